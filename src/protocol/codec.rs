@@ -0,0 +1,52 @@
+use super::{Decoder, Encoder};
+use bytes::IntoBuf;
+use error::Result;
+
+/// Convenience helpers layered over [`Encoder`]/[`Decoder`] for types that can be
+/// encoded/decoded in a single call, without the caller managing a buffer by hand.
+pub trait Codec: Encoder + Decoder + Default + Sized {
+    /// Encodes `self` into a freshly allocated `Vec<u8>`.
+    ///
+    /// Propagates any error `encode` returns (e.g. a value that doesn't fit the wire
+    /// format). Panics only if `encode` reports a length that does not match the
+    /// number of bytes actually written to the buffer; that mismatch means the
+    /// encoder itself has a bug, and silently returning a truncated or over-long
+    /// buffer would only move the failure somewhere harder to diagnose.
+    fn serialize(&self) -> Result<Vec<u8>> {
+        let mut dst = vec![];
+        let reported = self.encode(&mut dst)?;
+        assert_eq!(
+            reported,
+            dst.len(),
+            "Encoder::encode reported {} bytes but wrote {}",
+            reported,
+            dst.len()
+        );
+        Ok(dst)
+    }
+
+    /// Decodes a `Self` from the front of `src`, returning it along with the number of
+    /// bytes consumed.
+    fn deserialize(src: &[u8]) -> Result<(Self, usize)> {
+        let mut value = Self::default();
+        let n = value.decode(&mut src.into_buf())?;
+        Ok((value, n))
+    }
+}
+
+impl<T: Encoder + Decoder + Default> Codec for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::varint::VarInt;
+    use super::*;
+
+    #[test]
+    fn var_int_round_trips_through_serialize_and_deserialize() {
+        let v = VarInt::from_u64(16843009).unwrap();
+        let bytes = v.serialize().unwrap();
+        let (decoded, n) = VarInt::deserialize(&bytes).unwrap();
+        assert_eq!(decoded, v);
+        assert_eq!(n, bytes.len());
+    }
+}
@@ -0,0 +1,96 @@
+use super::varint::VarInt;
+use super::{Decoder, Encoder};
+use bytes::{Buf, BufMut, IntoBuf};
+use error::{ErrorKind, Result};
+
+/// Default ceiling on the length prefix of a [`decode_bytes`] call: 512 MiB.
+///
+/// QUIC frame bodies and transport parameters carry an attacker-controlled `VarInt`
+/// length ahead of their payload; honoring it verbatim would let a peer make us
+/// allocate or `reserve` gigabytes of memory for a handful of bytes on the wire.
+pub const DEFAULT_MAX_ALLOCATION_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Writes `src` as a QUIC length-delimited byte string: a `VarInt` length followed by
+/// the raw bytes.
+pub fn encode_bytes<T: BufMut>(dst: &mut T, src: &[u8]) -> Result<usize> {
+    let len = VarInt::from_u64(src.len() as u64)?;
+    let n = len.encode(dst)?;
+    dst.put_slice(src);
+    Ok(n + src.len())
+}
+
+/// Reads a QUIC length-delimited byte string written by [`encode_bytes`].
+///
+/// Returns an error instead of allocating when the decoded length exceeds
+/// [`DEFAULT_MAX_ALLOCATION_BYTES`]. Use [`decode_bytes_with_limit`] to customize the
+/// limit.
+pub fn decode_bytes<T: Buf>(src: &mut T) -> Result<Vec<u8>> {
+    decode_bytes_with_limit(src, DEFAULT_MAX_ALLOCATION_BYTES)
+}
+
+/// Like [`decode_bytes`], but with a caller-supplied `max_allocation_bytes` guard
+/// against malicious length prefixes.
+pub fn decode_bytes_with_limit<T: Buf>(src: &mut T, max_allocation_bytes: u64) -> Result<Vec<u8>> {
+    let mut len = VarInt::default();
+    len.decode(src)?;
+    let len = len.into_inner();
+    if len > max_allocation_bytes {
+        return Err(ErrorKind::AllocationTooLarge(len, max_allocation_bytes).into());
+    }
+    if (src.remaining() as u64) < len {
+        return Err(ErrorKind::UnexpectedEof(len, src.remaining() as u64).into());
+    }
+    let len = len as usize;
+    let mut buf = Vec::with_capacity(len);
+    buf.resize(len, 0);
+    src.copy_to_slice(&mut buf);
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_byte_slice() {
+        let mut dst = vec![];
+        let n = encode_bytes(&mut dst, b"hello quic").unwrap();
+        assert_eq!(n, dst.len());
+        let decoded = decode_bytes(&mut dst.into_buf()).unwrap();
+        assert_eq!(decoded, b"hello quic");
+    }
+
+    #[test]
+    fn round_trips_an_empty_slice() {
+        let mut dst = vec![];
+        encode_bytes(&mut dst, b"").unwrap();
+        let decoded = decode_bytes(&mut dst.into_buf()).unwrap();
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn rejects_a_length_over_the_allocation_limit() {
+        // A VarInt encoding of 1 GiB, with no payload bytes following.
+        let mut dst = vec![];
+        VarInt::from_u64(1024 * 1024 * 1024).unwrap().encode(&mut dst).unwrap();
+        let err = decode_bytes(&mut dst.into_buf());
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn honors_a_custom_allocation_limit() {
+        let mut dst = vec![];
+        encode_bytes(&mut dst, &[0u8; 16]).unwrap();
+        assert!(decode_bytes_with_limit(&mut dst.into_buf(), 8).is_err());
+    }
+
+    #[test]
+    fn rejects_a_length_prefix_longer_than_the_supplied_bytes() {
+        // The prefix claims 100 bytes follow, but only 10 are actually there.
+        let mut dst = vec![];
+        VarInt::from_u64(100).unwrap().encode(&mut dst).unwrap();
+        dst.extend_from_slice(&[0u8; 10]);
+        let err = decode_bytes(&mut dst.into_buf());
+        assert!(err.is_err());
+    }
+}
@@ -13,12 +13,69 @@ const INT_2_FLAG: u8 = 0b01;
 const INT_4_FLAG: u8 = 0b10;
 const INT_8_FLAG: u8 = 0b11;
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct VarInt(u64);
 
+impl VarInt {
+    /// The largest value representable by a QUIC variable-length integer, `2^62 - 1`.
+    pub const MAX: VarInt = VarInt(MAX_INT_8);
+
+    /// The number of bytes needed to encode `VarInt::MAX`.
+    pub const MAX_SIZE: usize = 8;
+
+    /// Constructs a `VarInt` from a `u64`, returning an error if it is too large to be
+    /// represented (i.e. does not fit in 62 bits).
+    pub fn from_u64(v: u64) -> Result<Self> {
+        if v > MAX_INT_8 {
+            return Err(ErrorKind::VarIntOutOfRange(v).into());
+        }
+        Ok(VarInt(v))
+    }
+
+    /// Constructs a `VarInt` from a `u32`. Always succeeds, since every `u32` fits in 62 bits.
+    pub fn from_u32(v: u32) -> Self {
+        VarInt(v as u64)
+    }
+
+    /// Extracts the inner `u64` value.
+    pub fn into_inner(self) -> u64 {
+        self.0
+    }
+
+    /// The number of bytes this value would occupy once encoded.
+    pub fn size(&self) -> usize {
+        match self.0 {
+            0..=MAX_INT_1 => 1,
+            0..=MAX_INT_2 => 2,
+            0..=MAX_INT_4 => 4,
+            _ => 8,
+        }
+    }
+}
+
 impl std::convert::From<u64> for VarInt {
+    /// Out-of-range values are clamped to `VarInt::MAX` rather than panicking. Prefer
+    /// `VarInt::from_u64` when the value comes from an untrusted source and the caller
+    /// should be able to react to the error instead.
     fn from(v: u64) -> VarInt {
-        VarInt(v)
+        if v > MAX_INT_8 {
+            VarInt::MAX
+        } else {
+            VarInt(v)
+        }
+    }
+}
+
+impl std::convert::From<u32> for VarInt {
+    fn from(v: u32) -> VarInt {
+        VarInt::from_u32(v)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for VarInt {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(VarInt(u.int_in_range(0..=MAX_INT_8)?))
     }
 }
 
@@ -41,17 +98,30 @@ impl Encoder for VarInt {
                 dst.put_uint_be(self.0 | ((INT_8_FLAG as u64) << 62), 8);
                 8
             }
-            v => panic!(
-                "variable-length integer {} has overflown, maximum is {}",
-                v, MAX_INT_8
-            ),
+            v => return Err(ErrorKind::VarIntOutOfRange(v).into()),
         })
     }
 }
 
 impl Decoder for VarInt {
     fn decode<T: Buf>(&mut self, src: &mut T) -> Result<usize> {
+        // `Buf::get_u8`/`get_uint_be` panic on a short buffer; since the flag byte and
+        // the trailing bytes it promises are both attacker-controlled, check
+        // `remaining()` ourselves and turn a truncated input into an `Err`.
+        if src.remaining() < 1 {
+            return Err(ErrorKind::UnexpectedEof(1, src.remaining() as u64).into());
+        }
         let first = src.get_u8();
+        let trailing = match first >> 6 {
+            INT_1_FLAG => 0,
+            INT_2_FLAG => 1,
+            INT_4_FLAG => 3,
+            INT_8_FLAG => 7,
+            _ => unreachable!(),
+        };
+        if src.remaining() < trailing {
+            return Err(ErrorKind::UnexpectedEof(trailing as u64, src.remaining() as u64).into());
+        }
         let (v, n) = match first >> 6 {
             INT_1_FLAG => ((first as u64) & MAX_INT_1, 1),
             INT_2_FLAG => (((first as u64) << 8 | src.get_uint_be(1)) & MAX_INT_2, 2),
@@ -59,6 +129,11 @@ impl Decoder for VarInt {
             INT_8_FLAG => (((first as u64) << 56 | src.get_uint_be(7)) & MAX_INT_8, 8),
             _ => unreachable!(),
         };
+        // QUIC requires varints to be encoded in the fewest bytes that can hold the
+        // value; a longer encoding is a protocol error rather than a lenient synonym.
+        if VarInt(v).size() != n {
+            return Err(ErrorKind::VarIntNotMinimal(v, n).into());
+        }
         self.0 = v;
         Ok(n)
     }
@@ -140,4 +215,80 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn max_is_2_pow_62_minus_1() {
+        assert_eq!(VarInt::MAX.into_inner(), (1u64 << 62) - 1);
+    }
+
+    #[test]
+    fn from_u64_rejects_out_of_range() {
+        assert!(VarInt::from_u64(1u64 << 62).is_err());
+        assert!(VarInt::from_u64((1u64 << 62) - 1).is_ok());
+    }
+
+    #[test]
+    fn size_matches_encoded_length() {
+        assert_eq!(VarInt::from(3u64).size(), 1);
+        assert_eq!(VarInt::from(257u64).size(), 2);
+        assert_eq!(VarInt::from(16843009u64).size(), 4);
+        assert_eq!(VarInt::from(72340172838076673u64).size(), 8);
+        assert_eq!(VarInt::MAX.size(), VarInt::MAX_SIZE);
+    }
+
+    #[test]
+    fn decode_of_encode_is_the_identity() {
+        for v in &[0u64, 1, 63, 64, 16383, 16384, 1073741823, 1073741824, MAX_INT_8] {
+            let input = VarInt(*v);
+            let mut dst = vec![];
+            input.encode(&mut dst).unwrap();
+
+            let mut output = VarInt::default();
+            let n = output.decode(&mut dst.into_buf()).unwrap();
+            assert_eq!(output, input);
+            assert_eq!(n, dst.len());
+        }
+    }
+
+    #[test]
+    fn decode_rejects_a_non_minimal_encoding() {
+        // 0 fits in a single byte but is here encoded with the two-byte flag.
+        let non_minimal = vec![0b01000000, 0b00000000];
+        let mut v = VarInt::default();
+        assert!(v.decode(&mut non_minimal.into_buf()).is_err());
+    }
+
+    #[test]
+    fn decode_rejects_an_empty_buffer() {
+        let empty: Vec<u8> = vec![];
+        let mut v = VarInt::default();
+        assert!(v.decode(&mut empty.into_buf()).is_err());
+    }
+
+    #[test]
+    fn decode_rejects_a_truncated_multi_byte_value() {
+        // The flag claims a 2-byte value, but only the flag byte is present.
+        let truncated = vec![0b01000001];
+        let mut v = VarInt::default();
+        assert!(v.decode(&mut truncated.into_buf()).is_err());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_values_stay_within_range_and_round_trip() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let data = [0xffu8; 64];
+        let mut u = Unstructured::new(&data);
+        for _ in 0..32 {
+            let input = VarInt::arbitrary(&mut u).unwrap();
+            assert!(input.into_inner() <= MAX_INT_8);
+
+            let mut dst = vec![];
+            input.encode(&mut dst).unwrap();
+            let mut output = VarInt::default();
+            output.decode(&mut dst.into_buf()).unwrap();
+            assert_eq!(output, input);
+        }
+    }
 }